@@ -1,15 +1,133 @@
 use std::{
     collections::HashMap,
+    fmt,
     path::{Path, PathBuf},
     time::SystemTime,
 };
-use tool_interface::{Tool, CreateToolFn};
+use tool_interface::{Tool, CreateToolFn, CreateToolsFn, ToolAbiInfo, ToolSink, AbiFn};
 use libloading::Library;
 
+/// `abi_version`/`interface_hash` this build of `tool_registry` expects plugins to report
+/// via the `tool_registry_abi` symbol. Bump `ABI_VERSION` on breaking `tool_interface`
+/// changes and `INTERFACE_HASH` whenever the `Tool` vtable layout changes shape.
+const ABI_VERSION: u32 = 1;
+const INTERFACE_HASH: u64 = 0x5a17_ed4b_c0de_face;
+
+/// Owned copy of [`ToolAbiInfo`] for storing in a [`ToolLoadError`].
+///
+/// `ToolAbiInfo::rustc_version` is a `&'static str` pointing into the plugin's own mapped
+/// memory, which is only "static" for as long as the library that reported it stays loaded.
+/// `LoadReport::failed` outlives the library it was reported from (the caller may inspect or
+/// log it long after the corresponding `dlclose`), so we copy `rustc_version` into an owned
+/// `String` immediately, while the library is still mapped and the pointer is still valid.
+#[derive(Debug, Clone)]
+pub struct ReportedAbi {
+    pub abi_version: u32,
+    pub interface_hash: u64,
+    pub rustc_version: String,
+}
+
+impl From<ToolAbiInfo> for ReportedAbi {
+    fn from(abi: ToolAbiInfo) -> Self {
+        ReportedAbi {
+            abi_version: abi.abi_version,
+            interface_hash: abi.interface_hash,
+            rustc_version: abi.rustc_version.to_string(),
+        }
+    }
+}
+
+/// Errors that can occur while loading a single shared library into the registry.
+#[derive(Debug)]
+pub enum ToolLoadError {
+    /// The path couldn't be opened as a shared library at all (wrong format, permissions, ...).
+    NotALibrary(libloading::Error),
+    /// A required symbol (`tool_registry_abi`, `create_tool`, ...) isn't exported.
+    MissingSymbol(&'static str),
+    /// The library's reported ABI doesn't match what this registry build expects.
+    AbiMismatch { found: ReportedAbi, expected_version: u32, expected_hash: u64 },
+    /// The plugin's constructor panicked instead of returning a tool. Only observable because
+    /// `CreateToolFn`/`CreateToolsFn` are required to use `extern "C-unwind"`; a plain `extern
+    /// "C"` constructor that panics aborts the whole process before this can be caught.
+    ConstructorPanicked,
+    /// The `libTOOL@ABIVERSION.so` filename tag didn't match this build's ABI version; the
+    /// file was skipped without being dlopen'd.
+    FilenameAbiMismatch { found: u32, expected: u32 },
+    /// Reading the library's file metadata failed.
+    Io(std::io::Error),
+}
+
+impl fmt::Display for ToolLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolLoadError::NotALibrary(err) => write!(f, "not a shared library: {err}"),
+            ToolLoadError::MissingSymbol(symbol) => {
+                write!(f, "library does not export a `{symbol}` symbol")
+            }
+            ToolLoadError::AbiMismatch { found, expected_version, expected_hash } => write!(
+                f,
+                "ABI mismatch: library reports abi_version={}, interface_hash={:#x} \
+                 (built with {}), but this registry expects abi_version={}, interface_hash={:#x}",
+                found.abi_version, found.interface_hash, found.rustc_version,
+                expected_version, expected_hash
+            ),
+            ToolLoadError::ConstructorPanicked => {
+                write!(f, "plugin constructor panicked instead of returning a tool")
+            }
+            ToolLoadError::FilenameAbiMismatch { found, expected } => write!(
+                f,
+                "filename declares abi_version={found}, but this registry expects \
+                 abi_version={expected}; skipped without opening the library"
+            ),
+            ToolLoadError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ToolLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ToolLoadError::NotALibrary(err) => Some(err),
+            ToolLoadError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ToolLoadError {
+    fn from(err: std::io::Error) -> Self {
+        ToolLoadError::Io(err)
+    }
+}
+
+/// Outcome of scanning one or more directories for tool libraries: every tool that loaded
+/// successfully, the `(tool name, path)` of every candidate shadowed by a higher-version one
+/// (only populated by [`ToolRegistry::load_from_paths`]), and the path and reason for every
+/// candidate that didn't load. A single malformed or incompatible library no longer blocks
+/// the good ones after it.
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    pub loaded: Vec<String>,
+    pub shadowed: Vec<(String, PathBuf)>,
+    pub failed: Vec<(PathBuf, ToolLoadError)>,
+}
+
+/// Collects the tools a plugin registers through `create_tools`, so a single library can
+/// hand over a whole bundle (e.g. a "filesystem tools" crate exposing read/write/list).
+#[derive(Default)]
+struct CollectingSink {
+    tools: Vec<Box<dyn Tool>>,
+}
+
+impl ToolSink for CollectingSink {
+    fn register(&mut self, tool: Box<dyn Tool>) {
+        self.tools.push(tool);
+    }
+}
 
 pub struct ToolRegistry {
     tools: HashMap<String, Box<dyn Tool>>,
-    loaded_libraries: HashMap<PathBuf, (SystemTime, Library)>,
+    loaded_libraries: HashMap<PathBuf, (SystemTime, Vec<String>, Library)>,
 }
 
 impl ToolRegistry {
@@ -33,73 +151,344 @@ impl ToolRegistry {
         }).collect()
     }
 
-    pub fn load_from_dir(&mut self, dir_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    /// Scans a directory for tool libraries and loads each one, continuing past any that
+    /// fail so one bad `.so` doesn't block every good one after it.
+    pub fn load_from_dir(&mut self, dir_path: &Path) -> Result<LoadReport, Box<dyn std::error::Error>> {
         let dir_entries = std::fs::read_dir(dir_path)?;
-        
+
+        let mut report = LoadReport::default();
         for entry in dir_entries {
             let entry = entry?;
             let path = entry.path();
-            
-            if is_shared_library(&path) {
-                self.load_library(&path)?;
+
+            if !is_shared_library(&path) {
+                continue;
+            }
+
+            if let Some((_, tagged_version)) = parse_filename_abi_tag(&path) {
+                if tagged_version != ABI_VERSION {
+                    report.failed.push((
+                        path,
+                        ToolLoadError::FilenameAbiMismatch { found: tagged_version, expected: ABI_VERSION },
+                    ));
+                    continue;
+                }
+            }
+
+            match self.load_library(&path) {
+                Ok(names) => report.loaded.extend(names),
+                Err(err) => report.failed.push((path, err)),
             }
         }
-        
-        Ok(())
+
+        Ok(report)
     }
 
     pub fn get_tool(&self, name: &str) -> Option<&Box<dyn Tool>> {
         self.tools.get(name)
     }
 
-    fn load_library(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    fn load_library(&mut self, path: &Path) -> Result<Vec<String>, ToolLoadError> {
         let metadata = std::fs::metadata(path)?;
         let modified = metadata.modified()?;
-        
-        if let Some((prev_modified, _)) = self.loaded_libraries.get(path) {
+
+        if let Some((prev_modified, _, _)) = self.loaded_libraries.get(path) {
             if &modified <= prev_modified {
-                return Ok(());
+                return Ok(Vec::new());
             }
             // Unload old version if we're reloading
             self.unload_library(path);
         }
-        
+
         unsafe {
-            let lib = Library::new(path)?;
-            let constructor: libloading::Symbol<CreateToolFn> = lib.get(b"create_tool")?;
+            let lib = Library::new(path).map_err(ToolLoadError::NotALibrary)?;
+            let tools = Self::construct_tools(&lib)?;
 
-            let tool_ptr = constructor();
-            let tool: Box<dyn Tool> = Box::from_raw(tool_ptr);
-            let name = tool.name().to_string();
-            
-            self.tools.insert(name, tool);
-            self.loaded_libraries.insert(path.to_path_buf(), (modified, lib));
+            let names: Vec<String> = tools.iter().map(|tool| tool.name().to_string()).collect();
+            for (name, tool) in names.iter().cloned().zip(tools) {
+                self.tools.insert(name, tool);
+            }
+            self.loaded_libraries
+                .insert(path.to_path_buf(), (modified, names.clone(), lib));
+
+            Ok(names)
+        }
+    }
+
+    /// Walks several directories and loads the tools they export, resolving same-named
+    /// candidates the way rustc's crate locator resolves same-named crates on its search
+    /// path: the highest [`Tool::version`] wins, and a tie for the highest version is a hard
+    /// error instead of filesystem-iteration-order roulette. Like [`Self::load_from_dir`], a
+    /// single malformed or ABI-incompatible candidate is recorded in the returned report's
+    /// `failed` list instead of aborting the whole scan.
+    pub fn load_from_paths(
+        &mut self,
+        dirs: &[PathBuf],
+    ) -> Result<LoadReport, Box<dyn std::error::Error>> {
+        // Field order matters: Rust drops struct fields in declaration order, and `tools`
+        // must be dropped before `lib` - its `Box<dyn Tool>` drop glue lives in the library's
+        // own code, so dropping `lib` first would run that glue through unmapped memory.
+        struct Candidate {
+            path: PathBuf,
+            modified: SystemTime,
+            tools: Vec<Option<Box<dyn Tool>>>,
+            lib: Library,
+        }
+
+        let mut report = LoadReport::default();
+        let mut candidates = Vec::new();
+        for dir in dirs {
+            for entry in std::fs::read_dir(dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                if !is_shared_library(&path) {
+                    continue;
+                }
+
+                if let Some((_, tagged_version)) = parse_filename_abi_tag(&path) {
+                    if tagged_version != ABI_VERSION {
+                        report.failed.push((
+                            path,
+                            ToolLoadError::FilenameAbiMismatch { found: tagged_version, expected: ABI_VERSION },
+                        ));
+                        continue;
+                    }
+                }
+
+                match Self::open_candidate(&path) {
+                    Ok((modified, lib, tools)) => candidates.push(Candidate {
+                        path,
+                        modified,
+                        lib,
+                        tools: tools.into_iter().map(Some).collect(),
+                    }),
+                    Err(err) => report.failed.push((path, err)),
+                }
+            }
+        }
+
+        let name_versions: Vec<Vec<(String, semver::Version)>> = candidates
+            .iter()
+            .map(|candidate| {
+                candidate
+                    .tools
+                    .iter()
+                    .map(|tool| {
+                        let tool = tool.as_ref().unwrap();
+                        (tool.name().to_string(), tool.version())
+                    })
+                    .collect()
+            })
+            .collect();
+        let (winners, shadowed) = resolve_winners(&name_versions).map_err(|tie| -> Box<dyn std::error::Error> {
+            format!(
+                "tool `{}` has two candidates of equal version {}: {:?} and {:?}",
+                tie.name, tie.version, candidates[tie.first].path, candidates[tie.second].path
+            )
+            .into()
+        })?;
+
+        for (name, ci) in shadowed {
+            report.shadowed.push((name, candidates[ci].path.clone()));
+        }
+
+        let mut winning_names: Vec<Vec<String>> = candidates.iter().map(|_| Vec::new()).collect();
+        for (name, winner_ci, winner_ti) in winners {
+            let tool = candidates[winner_ci].tools[winner_ti].take().unwrap();
+            self.tools.insert(name.clone(), tool);
+            report.loaded.push(name.clone());
+            winning_names[winner_ci].push(name);
+        }
+
+        for (candidate, names) in candidates.into_iter().zip(winning_names) {
+            if names.is_empty() {
+                // Every tool this library offered lost to a higher-version candidate.
+                continue;
+            }
+            self.loaded_libraries
+                .insert(candidate.path, (candidate.modified, names, candidate.lib));
+        }
+
+        Ok(report)
+    }
+
+    /// Opens a library and constructs its tools without taking ownership of them yet, so
+    /// [`Self::load_from_paths`] can stage candidates from several directories before
+    /// deciding which ones win a name collision.
+    fn open_candidate(
+        path: &Path,
+    ) -> Result<(SystemTime, Library, Vec<Box<dyn Tool>>), ToolLoadError> {
+        let modified = std::fs::metadata(path)?.modified()?;
+        let lib = unsafe { Library::new(path).map_err(ToolLoadError::NotALibrary)? };
+        let tools = unsafe { Self::construct_tools(&lib)? };
+        Ok((modified, lib, tools))
+    }
+
+    /// Resolves and validates the ABI handshake, then constructs every tool a library
+    /// exports without registering them - shared by single-directory and multi-path loads.
+    ///
+    /// `CreateToolFn`/`CreateToolsFn` are required to use the `extern "C-unwind"` ABI (part
+    /// of the same ABI contract `tool_registry_abi` advertises) rather than plain `extern
+    /// "C"`. A plain `extern "C"` boundary aborts the whole process the instant a panic tries
+    /// to cross it, no matter which thread is running it or how the call is wrapped - neither
+    /// `catch_unwind` nor off-thread isolation changes that. With `C-unwind`, a panicking
+    /// constructor unwinds normally up to this `catch_unwind`, which reports it as
+    /// [`ToolLoadError::ConstructorPanicked`] instead of taking the host down.
+    unsafe fn construct_tools(lib: &Library) -> Result<Vec<Box<dyn Tool>>, ToolLoadError> {
+        let abi_fn: libloading::Symbol<AbiFn> = lib
+            .get(b"tool_registry_abi")
+            .map_err(|_| ToolLoadError::MissingSymbol("tool_registry_abi"))?;
+        let abi = abi_fn();
+        if abi.abi_version != ABI_VERSION || abi.interface_hash != INTERFACE_HASH {
+            return Err(ToolLoadError::AbiMismatch {
+                found: ReportedAbi::from(*abi),
+                expected_version: ABI_VERSION,
+                expected_hash: INTERFACE_HASH,
+            });
+        }
+
+        // Prefer the plural constructor so a library can register a whole bundle of
+        // tools at once; fall back to the single-tool constructor for older plugins.
+        if let Ok(constructor) = lib.get::<CreateToolsFn>(b"create_tools") {
+            let mut sink = CollectingSink::default();
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| constructor(&mut sink)))
+                .map_err(|_| ToolLoadError::ConstructorPanicked)?;
+            Ok(sink.tools)
+        } else {
+            let constructor: libloading::Symbol<CreateToolFn> = lib
+                .get(b"create_tool")
+                .map_err(|_| ToolLoadError::MissingSymbol("create_tool"))?;
+            let tool_ptr = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| constructor()))
+                .map_err(|_| ToolLoadError::ConstructorPanicked)?;
+            Ok(vec![Box::from_raw(tool_ptr)])
         }
-        
-        Ok(())
     }
 
     fn unload_library(&mut self, path: &Path) {
-        if let Some((_, library)) = self.loaded_libraries.remove(path) {
-            // Find all tools from this library and remove them
-            let tools_to_remove: Vec<String> = self.tools.iter()
-                .filter(|(_, tool)| {
-                    // This is a simplistic approach - you might need a better way
-                    // to associate tools with their libraries
-                    true
-                })
-                .map(|(name, _)| name.clone())
-                .collect();
-            
-            for tool_name in tools_to_remove {
+        if let Some((_, tool_names, library)) = self.loaded_libraries.remove(path) {
+            // Drop the tool `Box`es before the `Library` - their vtables live in its code.
+            for tool_name in tool_names {
                 self.tools.remove(&tool_name);
             }
-            
-            // Library will be dropped here
+
+            drop(library);
         }
     }
 }
 
+/// An equal-version collision found by [`resolve_winners`]: `first` and `second` are the
+/// indices of the two tied candidates.
+struct VersionTie {
+    name: String,
+    version: semver::Version,
+    first: usize,
+    second: usize,
+}
+
+/// Given each candidate's exported `(name, version)` pairs, decides which `(candidate_index,
+/// tool_index)` wins each tool name - the highest [`semver::Version`] wins - and which lose.
+/// Returns the winners as `(name, candidate_index, tool_index)` and the losers as `(name,
+/// candidate_index)`. Two candidates tied for the highest version are a hard error. Pure and side-effect free
+/// so [`ToolRegistry::load_from_paths`]'s collision/shadowing logic can be unit tested
+/// without a real shared library.
+fn resolve_winners(
+    candidates: &[Vec<(String, semver::Version)>],
+) -> Result<(Vec<(String, usize, usize)>, Vec<(String, usize)>), VersionTie> {
+    let mut locations_by_name: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+    for (ci, tools) in candidates.iter().enumerate() {
+        for (ti, (name, _)) in tools.iter().enumerate() {
+            locations_by_name.entry(name.as_str()).or_default().push((ci, ti));
+        }
+    }
+
+    let mut winners = Vec::new();
+    let mut shadowed = Vec::new();
+    for (name, locations) in locations_by_name {
+        let mut best: Option<(usize, usize)> = None;
+        for &(ci, ti) in &locations {
+            let version = &candidates[ci][ti].1;
+            best = match best {
+                Some((best_ci, best_ti)) if *version <= candidates[best_ci][best_ti].1 => best,
+                _ => Some((ci, ti)),
+            };
+        }
+        let (winner_ci, winner_ti) = best.expect("at least one location per name");
+        let winning_version = &candidates[winner_ci][winner_ti].1;
+        for &(ci, ti) in &locations {
+            if (ci, ti) != (winner_ci, winner_ti) && candidates[ci][ti].1 == *winning_version {
+                return Err(VersionTie {
+                    name: name.to_string(),
+                    version: winning_version.clone(),
+                    first: winner_ci,
+                    second: ci,
+                });
+            }
+        }
+
+        for (ci, ti) in locations {
+            if (ci, ti) != (winner_ci, winner_ti) {
+                shadowed.push((name.to_string(), ci));
+            }
+        }
+        winners.push((name.to_string(), winner_ci, winner_ti));
+    }
+
+    Ok((winners, shadowed))
+}
+
+#[cfg(test)]
+mod resolve_winners_tests {
+    use super::resolve_winners;
+    use semver::Version;
+
+    #[test]
+    fn highest_version_wins_and_loser_is_shadowed() {
+        let candidates = vec![
+            vec![("read_file".to_string(), Version::new(1, 0, 0))],
+            vec![("read_file".to_string(), Version::new(2, 0, 0))],
+        ];
+
+        let (winners, shadowed) = resolve_winners(&candidates).unwrap();
+
+        assert_eq!(winners, vec![("read_file".to_string(), 1, 0)]);
+        assert_eq!(shadowed, vec![("read_file".to_string(), 0)]);
+    }
+
+    #[test]
+    fn equal_versions_are_a_hard_error() {
+        let candidates = vec![
+            vec![("read_file".to_string(), Version::new(1, 0, 0))],
+            vec![("read_file".to_string(), Version::new(1, 0, 0))],
+        ];
+
+        let tie = resolve_winners(&candidates).unwrap_err();
+
+        assert_eq!(tie.name, "read_file");
+        assert_eq!(tie.version, Version::new(1, 0, 0));
+        assert_eq!((tie.first, tie.second), (0, 1));
+    }
+
+    #[test]
+    fn distinct_names_all_win_without_shadowing() {
+        let candidates = vec![
+            vec![("read_file".to_string(), Version::new(1, 0, 0))],
+            vec![("write_file".to_string(), Version::new(1, 0, 0))],
+        ];
+
+        let (mut winners, shadowed) = resolve_winners(&candidates).unwrap();
+        winners.sort();
+
+        assert_eq!(
+            winners,
+            vec![
+                ("read_file".to_string(), 0, 0),
+                ("write_file".to_string(), 1, 0),
+            ]
+        );
+        assert!(shadowed.is_empty());
+    }
+}
+
 fn is_shared_library(path: &Path) -> bool {
     let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
     cfg_if::cfg_if! {
@@ -111,4 +500,48 @@ fn is_shared_library(path: &Path) -> bool {
             ext.eq_ignore_ascii_case("so")
         }
     }
+}
+
+/// Parses the opt-in `libTOOL@ABIVERSION.so` filename convention (mirroring the strict
+/// `PREFIX name @ toolchain SUFFIX` form dylint requires of its artifacts), returning the
+/// tagged tool name and ABI version. Filenames that don't use the convention return `None`
+/// and are loaded the normal way, with the ABI check happening at dlopen time instead.
+fn parse_filename_abi_tag(path: &Path) -> Option<(&str, u32)> {
+    let stem = path.file_stem()?.to_str()?;
+    let stem = stem.strip_prefix("lib").unwrap_or(stem);
+    let (name, version) = stem.split_once('@')?;
+    let version = version.parse().ok()?;
+    Some((name, version))
+}
+
+#[cfg(test)]
+mod parse_filename_abi_tag_tests {
+    use super::parse_filename_abi_tag;
+    use std::path::Path;
+
+    #[test]
+    fn parses_the_conventional_form() {
+        assert_eq!(
+            parse_filename_abi_tag(Path::new("libfs_tools@3.so")),
+            Some(("fs_tools", 3))
+        );
+    }
+
+    #[test]
+    fn works_without_the_lib_prefix() {
+        assert_eq!(
+            parse_filename_abi_tag(Path::new("fs_tools@3.so")),
+            Some(("fs_tools", 3))
+        );
+    }
+
+    #[test]
+    fn missing_at_sign_is_not_the_convention() {
+        assert_eq!(parse_filename_abi_tag(Path::new("libfs_tools.so")), None);
+    }
+
+    #[test]
+    fn non_numeric_version_is_not_the_convention() {
+        assert_eq!(parse_filename_abi_tag(Path::new("libfs_tools@latest.so")), None);
+    }
 }
\ No newline at end of file